@@ -1,9 +1,12 @@
 use std::borrow::Cow;
 use thiserror::Error;
 
-enum State { Normal, Escape, StartUnicode, InUnicode }
+use super::diagnostic::Diagnostic;
+
+enum State { Normal, Escape, StartUnicode, InUnicode, InOctal }
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum UnescapeError {
     #[error("Bad escape parsing string. Failed at: {0}{1}")]
     BadEscape(String, char),
@@ -14,27 +17,190 @@ pub enum UnescapeError {
     #[error("Hex value too large in \\u. Failed at: {0}{1}")]
     HexValueTooLarge(String, char),
     #[error("Invalid value in \\u. Failed at: {0}{1}")]
-    InvalidUnicodeValue(String, char)
+    InvalidUnicodeValue(String, char),
+    #[error("Octal value too large. Failed at: {0}{1}")]
+    OctalValueTooLarge(String, char),
+    #[error("String ends inside an escape. Failed at: {0}")]
+    UnexpectedEnd(String)
 }
 
+impl UnescapeError {
+    /// The input up to the point of failure.
+    pub fn context(&self) -> &str {
+        match self {
+            UnescapeError::BadEscape(context, _)
+            | UnescapeError::MissingOpenBrace(context, _)
+            | UnescapeError::NonHexDigit(context, _)
+            | UnescapeError::HexValueTooLarge(context, _)
+            | UnescapeError::InvalidUnicodeValue(context, _)
+            | UnescapeError::OctalValueTooLarge(context, _)
+            | UnescapeError::UnexpectedEnd(context) => context,
+        }
+    }
 
-pub fn unescape<'a>(input: &'a str) -> anyhow::Result<Cow<'a, str>> {
-    let mut state = State::Normal;
-    let mut escape_sequence_seen = false;
+    /// The character at which unescaping failed, or `None` if the input ran out first.
+    pub fn found(&self) -> Option<char> {
+        match self {
+            UnescapeError::BadEscape(_, found)
+            | UnescapeError::MissingOpenBrace(_, found)
+            | UnescapeError::NonHexDigit(_, found)
+            | UnescapeError::HexValueTooLarge(_, found)
+            | UnescapeError::InvalidUnicodeValue(_, found)
+            | UnescapeError::OctalValueTooLarge(_, found) => Some(*found),
+            UnescapeError::UnexpectedEnd(_) => None,
+        }
+    }
+}
+
+impl Diagnostic for UnescapeError {
+    fn code(&self) -> &'static str {
+        match self {
+            UnescapeError::BadEscape(..) => "CS0001",
+            UnescapeError::MissingOpenBrace(..) => "CS0002",
+            UnescapeError::NonHexDigit(..) => "CS0003",
+            UnescapeError::HexValueTooLarge(..) => "CS0004",
+            UnescapeError::InvalidUnicodeValue(..) => "CS0005",
+            UnescapeError::OctalValueTooLarge(..) => "CS0006",
+            UnescapeError::UnexpectedEnd(..) => "CS0007",
+        }
+    }
+
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![("context", self.context().to_string())];
+        if let Some(found) = self.found() {
+            fields.push(("found", found.to_string()));
+        }
+        fields
+    }
+}
+
+/// Settings for [`unescape_with_options`]. The default matches [`unescape`]. Outside this
+/// crate, build options with [`new`](Self::new) and the setters, e.g.
+/// `UnescapeOptions::new().c_compat(true)`, since more fields may be added.
+#[derive(Debug, Default, Clone, Copy)]
+#[non_exhaustive]
+pub struct UnescapeOptions {
+    /// Decode `\u{…}` escapes naming a surrogate or a value past U+10FFFF as U+FFFD
+    /// and record a warning instead of failing.
+    pub replace_invalid_scalars: bool,
+    /// Accept C-style octal escapes of one to three digits (`\101`, `\0`). As in C, an
+    /// octal escape names a single byte, so values past `\377` are an error; the byte is
+    /// taken as a Latin-1 code point.
+    pub c_compat: bool,
+}
+
+impl UnescapeOptions {
+    /// The default options, matching [`unescape`].
+    pub fn new() -> UnescapeOptions {
+        UnescapeOptions::default()
+    }
+
+    /// Set [`replace_invalid_scalars`](Self::replace_invalid_scalars).
+    pub fn replace_invalid_scalars(mut self, replace_invalid_scalars: bool) -> UnescapeOptions {
+        self.replace_invalid_scalars = replace_invalid_scalars;
+        self
+    }
+
+    /// Set [`c_compat`](Self::c_compat).
+    pub fn c_compat(mut self, c_compat: bool) -> UnescapeOptions {
+        self.c_compat = c_compat;
+        self
+    }
+}
+
+pub fn unescape<'a>(input: &'a str) -> Result<Cow<'a, str>, UnescapeError> {
+    unescape_with_options(input, &UnescapeOptions::default(), &mut Vec::new())
+}
+
+/// Unescape `input` according to `options`. Problems which `options` asks to tolerate are
+/// pushed onto `warnings` rather than returned as errors.
+pub fn unescape_with_options<'a>(input: &'a str, options: &UnescapeOptions, warnings: &mut Vec<UnescapeError>) -> Result<Cow<'a, str>, UnescapeError> {
     // unescaping is guaranteed to have a length ≤ the input length
     let mut modified_string = String::with_capacity(input.len());
+    if decode(input, options, warnings, &mut modified_string)? {
+        Ok(modified_string.into())
+    }
+    else {
+        Ok(input.into())
+    }
+}
+
+/// The length in bytes of `unescape(input)`, computed without allocating.
+pub fn unescaped_len(input: &str) -> Result<usize, UnescapeError> {
+    unescaped_len_with_options(input, &UnescapeOptions::default(), &mut Vec::new())
+}
+
+/// The length in bytes of `unescape_with_options(input, options, warnings)`. Only
+/// `warnings` is ever allocated.
+pub fn unescaped_len_with_options(input: &str, options: &UnescapeOptions, warnings: &mut Vec<UnescapeError>) -> Result<usize, UnescapeError> {
+    let mut len = UnescapedLen(0);
+    if decode(input, options, warnings, &mut len)? {
+        Ok(len.0)
+    }
+    else {
+        Ok(input.len())
+    }
+}
+
+/// Check that `unescape(input)` would succeed, without allocating.
+pub fn validate_escapes(input: &str) -> Result<(), UnescapeError> {
+    validate_escapes_with_options(input, &UnescapeOptions::default(), &mut Vec::new())
+}
+
+/// Check that `unescape_with_options(input, options, warnings)` would succeed. Only
+/// `warnings` is ever allocated.
+pub fn validate_escapes_with_options(input: &str, options: &UnescapeOptions, warnings: &mut Vec<UnescapeError>) -> Result<(), UnescapeError> {
+    decode(input, options, warnings, &mut ())?;
+    Ok(())
+}
+
+/// Where `decode` sends its output.
+trait Output {
+    fn push(&mut self, c: char);
+    fn push_str(&mut self, s: &str);
+}
+
+impl Output for String {
+    fn push(&mut self, c: char) {
+        String::push(self, c)
+    }
+    fn push_str(&mut self, s: &str) {
+        String::push_str(self, s)
+    }
+}
+
+struct UnescapedLen(usize);
+
+impl Output for UnescapedLen {
+    fn push(&mut self, c: char) {
+        self.0 += c.len_utf8()
+    }
+    fn push_str(&mut self, s: &str) {
+        self.0 += s.len()
+    }
+}
+
+impl Output for () {
+    fn push(&mut self, _: char) {}
+    fn push_str(&mut self, _: &str) {}
+}
+
+/// The unescaping state machine shared by the public functions. Output is only written
+/// once an escape sequence has been seen; the return value says whether one was.
+fn decode<O: Output>(input: &str, options: &UnescapeOptions, warnings: &mut Vec<UnescapeError>, modified_string: &mut O) -> Result<bool, UnescapeError> {
+    let mut state = State::Normal;
+    let mut escape_sequence_seen = false;
     let mut unicode_value = 0u32;
+    let mut octal_value = 0u32;
+    let mut octal_digits = 0;
 
     for c in input.char_indices() {
         match state {
             State::Normal => {
                 match c {
                     (index,'\\') => {
-                        if !escape_sequence_seen {
-                            if index > 0 {
-                                // if unwrap panics, something is wrong with this code
-                                modified_string.push_str(input.get(0.. index).unwrap());
-                            }
+                        if !escape_sequence_seen && index > 0 {
+                            modified_string.push_str(&input[..index]);
                         }
                         escape_sequence_seen = true;
                         state = State::Escape;
@@ -48,34 +214,94 @@ pub fn unescape<'a>(input: &'a str) -> anyhow::Result<Cow<'a, str>> {
             }
             State::Escape => {
                 match c {
-                    (_, 't') => modified_string.push('\t'),
-                    (_, '\\') => modified_string.push('\\'),
-                    (_, '"') => modified_string.push('"'),
-                    (_, '\'') => modified_string.push('\''),
-                    (_, 'n') => modified_string.push('\n'),
-                    (_, 'r') => modified_string.push('\r'),
+                    (_, 't') => {
+                        modified_string.push('\t');
+                        state = State::Normal;
+                    }
+                    (_, '\\') => {
+                        modified_string.push('\\');
+                        state = State::Normal;
+                    }
+                    (_, '"') => {
+                        modified_string.push('"');
+                        state = State::Normal;
+                    }
+                    (_, '\'') => {
+                        modified_string.push('\'');
+                        state = State::Normal;
+                    }
+                    (_, 'n') => {
+                        modified_string.push('\n');
+                        state = State::Normal;
+                    }
+                    (_, 'r') => {
+                        modified_string.push('\r');
+                        state = State::Normal;
+                    }
                     (_, 'u') => state = State::StartUnicode,
+                    (_, ch @ '0'..='7') if options.c_compat => {
+                        octal_value = ch as u32 - '0' as u32;
+                        octal_digits = 1;
+                        state = State::InOctal;
+                    }
                     (index, ch) => {
-                        anyhow::bail!(UnescapeError::BadEscape(input.get(0..index).unwrap().to_string(), ch));
+                        return Err(UnescapeError::BadEscape(input[..index].to_string(), ch));
                     }
                 }
             }
 
             State::StartUnicode => {
                 if c.1 != '{' {
-                    anyhow::bail!(UnescapeError::MissingOpenBrace(input.get(0..c.0).unwrap().to_string(), c.1));
+                    return Err(UnescapeError::MissingOpenBrace(input[..c.0].to_string(), c.1));
                 }
                 unicode_value = 0;
                 state = State::InUnicode;
             }
 
+            State::InOctal => {
+                match c {
+                    (index, ch @ '0'..='7') => {
+                        octal_value = (octal_value << 3) + (ch as u32 - '0' as u32);
+                        octal_digits += 1;
+                        if octal_digits == 3 {
+                            if octal_value > 0o377 {
+                                return Err(UnescapeError::OctalValueTooLarge(input[..index].to_string(), ch));
+                            }
+                            modified_string.push(octal_char(octal_value));
+                            state = State::Normal;
+                        }
+                    }
+                    (_, '\\') => {
+                        modified_string.push(octal_char(octal_value));
+                        state = State::Escape;
+                    }
+                    (_, ch) => {
+                        modified_string.push(octal_char(octal_value));
+                        modified_string.push(ch);
+                        state = State::Normal;
+                    }
+                }
+            }
+
             State::InUnicode => {
                 match c {
+                    (_, '}') if unicode_value > 0x10FFFF => {
+                        // only reachable with replace_invalid_scalars; the warning was recorded
+                        // when the value overflowed
+                        modified_string.push(char::REPLACEMENT_CHARACTER);
+                        state = State::Normal;
+                    }
                     (index, '}') => {
                         let possible_char = char::from_u32(unicode_value);
                         match possible_char {
                             None => {
-                                anyhow::bail!(UnescapeError::InvalidUnicodeValue(input.get(0..index).unwrap().to_string(),'}'));
+                                let error = UnescapeError::InvalidUnicodeValue(input[..index].to_string(),'}');
+                                if !options.replace_invalid_scalars {
+                                    return Err(error);
+                                }
+                                warnings.push(error);
+                                modified_string.push(char::REPLACEMENT_CHARACTER);
+                                state = State::Normal;
                             }
                             Some(valid_char) => {
                                 modified_string.push(valid_char);
@@ -87,12 +313,19 @@ pub fn unescape<'a>(input: &'a str) -> anyhow::Result<Cow<'a, str>> {
                         let digit = ch.to_digit(0x10);
                         match digit {
                             None => {
-                                anyhow::bail!(UnescapeError::NonHexDigit(input.get(0..index).unwrap().to_string(), ch));
+                                return Err(UnescapeError::NonHexDigit(input[..index].to_string(), ch));
                             }
                             Some(d) => {
-                                unicode_value = (unicode_value << 4) + d;
-                                if unicode_value > 0x10FFFF {
-                                    anyhow::bail!(UnescapeError::HexValueTooLarge(input.get(0..index).unwrap().to_string(), ch))
+                                // once past the maximum, further digits can only be swallowed
+                                if unicode_value <= 0x10FFFF {
+                                    unicode_value = (unicode_value << 4) + d;
+                                    if unicode_value > 0x10FFFF {
+                                        let error = UnescapeError::HexValueTooLarge(input[..index].to_string(), ch);
+                                        if !options.replace_invalid_scalars {
+                                            return Err(error);
+                                        }
+                                        warnings.push(error);
+                                    }
                                 }
                             }
                         }
@@ -103,17 +336,26 @@ pub fn unescape<'a>(input: &'a str) -> anyhow::Result<Cow<'a, str>> {
         }
     }
 
-    if escape_sequence_seen {
-        Ok(modified_string.into())
-    }
-    else {
-        Ok(input.into())
+    match state {
+        State::Normal => {}
+        State::InOctal => modified_string.push(octal_char(octal_value)),
+        State::Escape | State::StartUnicode | State::InUnicode => {
+            return Err(UnescapeError::UnexpectedEnd(input.to_string()));
+        }
     }
+
+    Ok(escape_sequence_seen)
+}
+
+fn octal_char(value: u32) -> char {
+    // callers have checked the value is at most \377
+    char::from(value as u8)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     #[test]
     fn ordinary_string_comes_back_the_same() -> anyhow::Result<()> {
@@ -156,42 +398,42 @@ mod tests {
     #[test]
     fn bad_escape_gives_error() {
         let result = unescape("foo \\0");
-        assert_eq!(true, result.is_err());
+        assert!(result.is_err());
         assert_eq!("Bad escape parsing string. Failed at: foo \\0", format!("{}", result.err().unwrap()))
     }
 
     #[test]
     fn missing_brace_after_u_gives_error() {
         let result = unescape("foo \\un");
-        assert_eq!(true, result.is_err());
+        assert!(result.is_err());
         assert_eq!("Missing open brace after \\u. Failed at: foo \\un", format!("{}", result.err().unwrap()))
     }
 
     #[test]
     fn non_hex_digit_after_u_gives_error() {
         let result = unescape("foo \\u{n}");
-        assert_eq!(true, result.is_err());
+        assert!(result.is_err());
         assert_eq!("Non-hex digit in \\u. Failed at: foo \\u{n", format!("{}", result.err().unwrap()))
     }
 
     #[test]
     fn too_many_hex_digits_after_u_gives_error() {
         let result = unescape("foo \\u{1000000}");
-        assert_eq!(true, result.is_err());
+        assert!(result.is_err());
         assert_eq!("Hex value too large in \\u. Failed at: foo \\u{1000000", format!("{}", result.err().unwrap()))
     }
 
     #[test]
     fn too_large_a_value_gives_error() {
         let result = unescape("foo \\u{120000}");
-        assert_eq!(true, result.is_err());
+        assert!(result.is_err());
         assert_eq!("Hex value too large in \\u. Failed at: foo \\u{120000", format!("{}", result.err().unwrap()))
     }
 
     #[test]
     fn invalid_code_point_gives_error() {
         let result = unescape("foo \\u{d800}");
-        assert_eq!(true, result.is_err());
+        assert!(result.is_err());
         assert_eq!("Invalid value in \\u. Failed at: foo \\u{d800}", format!("{}", result.err().unwrap()))
     }
 
@@ -200,4 +442,186 @@ mod tests {
         assert_eq!("a\u{a0}b", unescape("a\\u{a0}b")? );
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn surrogate_is_replaced_when_requested() -> anyhow::Result<()> {
+        let options = UnescapeOptions { replace_invalid_scalars: true, ..Default::default() };
+        let mut warnings = Vec::new();
+        assert_eq!("a\u{fffd}b", unescape_with_options("a\\u{d800}b", &options, &mut warnings)?);
+        assert_eq!(1, warnings.len());
+        assert_eq!("Invalid value in \\u. Failed at: a\\u{d800}", format!("{}", warnings[0]));
+        Ok(())
+    }
+
+    #[test]
+    fn too_large_a_value_is_replaced_when_requested() -> anyhow::Result<()> {
+        let options = UnescapeOptions { replace_invalid_scalars: true, ..Default::default() };
+        let mut warnings = Vec::new();
+        assert_eq!("a\u{fffd}b", unescape_with_options("a\\u{123456789abc}b", &options, &mut warnings)?);
+        assert_eq!(1, warnings.len());
+        assert_eq!("Hex value too large in \\u. Failed at: a\\u{123456", format!("{}", warnings[0]));
+        Ok(())
+    }
+
+    #[test]
+    fn unescape_with_options_returns_typed_error() {
+        let error = unescape_with_options("foo \\q", &UnescapeOptions::default(), &mut Vec::new()).unwrap_err();
+        assert_eq!("CS0001", error.code());
+        assert_eq!("foo \\", error.context());
+        assert_eq!(Some('q'), error.found());
+        assert!(matches!(error, UnescapeError::BadEscape(ref context, 'q') if context == "foo \\"));
+    }
+
+    #[test]
+    fn octal_escape_is_decoded_in_c_compat_mode() -> anyhow::Result<()> {
+        let options = UnescapeOptions { c_compat: true, ..Default::default() };
+        assert_eq!("A", unescape_with_options("\\101", &options, &mut Vec::new())?);
+        assert_eq!("caf\u{e9}s", unescape_with_options("caf\\351s", &options, &mut Vec::new())?);
+        assert_eq!("\u{0}x\t", unescape_with_options("\\0x\\t", &options, &mut Vec::new())?);
+        assert_eq!("A2", unescape_with_options("\\1012", &options, &mut Vec::new())?);
+        assert_eq!("\u{7}\u{1}", unescape_with_options("\\7\\1", &options, &mut Vec::new())?);
+        assert_eq!("\u{ff}", unescape_with_options("\\377", &options, &mut Vec::new())?);
+        Ok(())
+    }
+
+    #[test]
+    fn octal_value_past_a_byte_gives_error() {
+        let options = UnescapeOptions { c_compat: true, ..Default::default() };
+        let result = unescape_with_options("foo \\400", &options, &mut Vec::new());
+        assert!(result.is_err());
+        assert_eq!("Octal value too large. Failed at: foo \\400", format!("{}", result.err().unwrap()))
+    }
+
+    #[test]
+    fn octal_escape_is_rejected_by_default() {
+        assert!(unescape("\\101").is_err());
+    }
+
+    #[test]
+    fn unescaped_len_matches_unescape() -> anyhow::Result<()> {
+        for input in &["ordinary", "a\\u{a0}b", "\\u{1f600}", "caf\u{e9} \\\\"] {
+            assert_eq!(unescape(input)?.len(), unescaped_len(input)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn validate_escapes_reports_errors() {
+        assert!(validate_escapes("a\\u{a0}b").is_ok());
+        let result = validate_escapes("foo \\0");
+        assert!(result.is_err());
+        assert_eq!("Bad escape parsing string. Failed at: foo \\0", format!("{}", result.err().unwrap()))
+    }
+
+    #[test]
+    fn unescaped_len_and_validation_follow_options() -> anyhow::Result<()> {
+        assert!(unescaped_len("a\\u{d800}").is_err());
+        assert!(validate_escapes("\\101").is_err());
+        let options = UnescapeOptions { replace_invalid_scalars: true, c_compat: true };
+        let mut warnings = Vec::new();
+        assert_eq!(unescape_with_options("a\\u{d800}\\101", &options, &mut Vec::new())?.len(),
+                   unescaped_len_with_options("a\\u{d800}\\101", &options, &mut warnings)?);
+        assert_eq!(1, warnings.len());
+        assert!(validate_escapes_with_options("\\101", &options, &mut Vec::new()).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn errors_have_stable_codes() {
+        assert_eq!("CS0001", UnescapeError::BadEscape(String::new(), '0').code());
+        assert_eq!("CS0005", UnescapeError::InvalidUnicodeValue(String::new(), '}').code());
+    }
+
+    #[test]
+    fn error_serializes_to_json() {
+        let error = UnescapeError::BadEscape("say \"foo \\".to_string(), '0');
+        assert_eq!(r#"{"code":"CS0001","message":"Bad escape parsing string. Failed at: say \"foo \\0","context":"say \"foo \\","found":"0"}"#,
+                   error.to_json());
+        let error = UnescapeError::NonHexDigit("\\u{".to_string(), '\t');
+        assert_eq!(r#"{"code":"CS0003","message":"Non-hex digit in \\u. Failed at: \\u{\t","context":"\\u{","found":"\t"}"#,
+                   error.to_json());
+    }
+
+    #[test]
+    fn error_is_rendered_from_catalog() {
+        let mut catalog = HashMap::new();
+        catalog.insert("CS0001", "Séquence d’échappement invalide « \\{found} » après : {context}");
+        let error = UnescapeError::BadEscape("foo ".to_string(), '0');
+        assert_eq!("Séquence d’échappement invalide « \\0 » après : foo ", error.localize(&catalog));
+        let error = UnescapeError::NonHexDigit("foo \\u{".to_string(), 'n');
+        assert_eq!("Non-hex digit in \\u. Failed at: foo \\u{n", error.localize(&catalog));
+    }
+
+    #[test]
+    fn catalog_values_are_not_substituted_again() {
+        let mut catalog = HashMap::new();
+        catalog.insert("CS0001", "bad escape after: {context} (char {found}) {unknown} {found");
+        let error = unescape_with_options("x{found}\\q", &UnescapeOptions::default(), &mut Vec::new()).unwrap_err();
+        assert_eq!("bad escape after: x{found}\\ (char q) {unknown} {found", error.localize(&catalog));
+    }
+
+    #[test]
+    fn text_after_escape_is_kept() -> anyhow::Result<()> {
+        assert_eq!("a\tb\nc\\d", unescape("a\\tb\\nc\\\\d")?);
+        Ok(())
+    }
+
+    #[test]
+    fn multibyte_context_is_reported() {
+        let error = unescape("é\\q").unwrap_err();
+        assert_eq!("Bad escape parsing string. Failed at: é\\q", format!("{}", error));
+        let error = unescape("日本\\u語").unwrap_err();
+        assert_eq!("CS0002", error.code());
+        assert_eq!("日本\\u", error.context());
+    }
+
+    #[test]
+    fn multibyte_char_in_unicode_escape_gives_error() {
+        let error = unescape("\\u{é}").unwrap_err();
+        assert_eq!("CS0003", error.code());
+        assert_eq!("\\u{", error.context());
+        assert_eq!(Some('é'), error.found());
+    }
+
+    #[test]
+    fn octal_error_after_multibyte_text_is_reported() {
+        let options = UnescapeOptions { c_compat: true, ..UnescapeOptions::default() };
+        let error = unescape_with_options("é\\400", &options, &mut Vec::new()).unwrap_err();
+        assert_eq!("é\\40", error.context());
+        assert_eq!(Some('0'), error.found());
+    }
+
+    #[test]
+    fn unterminated_escape_gives_error() {
+        for input in &["foo\\", "foo \\u", "foo \\u{", "foo \\u{a0"] {
+            let error = unescape(input).unwrap_err();
+            assert_eq!(format!("String ends inside an escape. Failed at: {}", input), format!("{}", error));
+            assert_eq!(None, error.found());
+        }
+    }
+
+    #[test]
+    fn backslash_after_octal_escape_gives_error() {
+        let options = UnescapeOptions { c_compat: true, ..UnescapeOptions::default() };
+        let error = unescape_with_options("\\101\\", &options, &mut Vec::new()).unwrap_err();
+        assert_eq!("CS0007", error.code());
+        assert_eq!("\\101\\", error.context());
+    }
+
+    #[test]
+    fn unexpected_end_serializes_without_found() {
+        let error = unescape("é\\u{").unwrap_err();
+        assert_eq!(r#"{"code":"CS0007","message":"String ends inside an escape. Failed at: é\\u{","context":"é\\u{"}"#,
+                   error.to_json());
+    }
+
+    #[test]
+    fn options_are_built_with_setters() -> anyhow::Result<()> {
+        let options = UnescapeOptions::new().c_compat(true).replace_invalid_scalars(true);
+        let mut warnings = Vec::new();
+        assert_eq!("A\u{fffd}", unescape_with_options("\\101\\u{d800}", &options, &mut warnings)?);
+        assert_eq!(1, warnings.len());
+        assert!(!UnescapeOptions::new().c_compat(true).c_compat(false).c_compat);
+        Ok(())
+    }
+}
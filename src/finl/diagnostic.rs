@@ -0,0 +1,90 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+/// What every error the crate returns provides beyond its English `Display` message, so
+/// editor plugins and CI can consume errors without parsing messages.
+pub trait Diagnostic: fmt::Display {
+    /// A stable code for the kind of error (`CS0001` etc.), for tools which shouldn't match
+    /// on messages.
+    fn code(&self) -> &'static str;
+
+    /// The values the error carries, named as message templates refer to them.
+    fn fields(&self) -> Vec<(&'static str, String)>;
+
+    /// Render the error with the template `catalog` has for its [`code`](Self::code),
+    /// substituting `{name}` for each of its [`fields`](Self::fields). Falls back to the
+    /// English message when the catalog has no template for the code.
+    fn localize(&self, catalog: &dyn MessageCatalog) -> String {
+        match catalog.template(self.code()) {
+            Some(template) => fill_template(template, &self.fields()),
+            None => self.to_string()
+        }
+    }
+
+    /// The error as a JSON object with `code` and `message` members followed by its fields.
+    fn to_json(&self) -> String {
+        let mut json = format!("{{\"code\":{},\"message\":{}",
+                               json_string(self.code()),
+                               json_string(&self.to_string()));
+        for (name, value) in self.fields() {
+            json.push_str(&format!(",{}:{}", json_string(name), json_string(&value)));
+        }
+        json.push('}');
+        json
+    }
+}
+
+/// A source of translated message templates, keyed by error code (`CS0001` etc.).
+pub trait MessageCatalog {
+    fn template(&self, code: &str) -> Option<&str>;
+}
+
+impl<K, V, S> MessageCatalog for HashMap<K, V, S>
+    where K: Borrow<str> + Hash + Eq, V: AsRef<str>, S: BuildHasher {
+    fn template(&self, code: &str) -> Option<&str> {
+        self.get(code).map(AsRef::as_ref)
+    }
+}
+
+/// Replace each `{name}` in `template` with the value of that field, in a single pass so
+/// that braces inside the values are left alone. Other text is copied as is.
+fn fill_template(template: &str, fields: &[(&str, String)]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        filled.push_str(&rest[..open]);
+        rest = &rest[open + 1..];
+        let field = fields.iter().find(|(name, _)| {
+            rest.strip_prefix(name).is_some_and(|after| after.starts_with('}'))
+        });
+        match field {
+            Some((name, value)) => {
+                filled.push_str(value);
+                rest = &rest[name.len() + 1..];
+            }
+            None => filled.push('{')
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
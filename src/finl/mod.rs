@@ -1 +1,6 @@
-mod charsub;
\ No newline at end of file
+pub mod charsub;
+pub mod diagnostic;
+#[cfg(feature = "unstable")]
+pub mod mojibake;
+#[cfg(feature = "unstable")]
+pub mod tokensub;
@@ -0,0 +1,126 @@
+/// A sequence in the input which looks like UTF-8 that was decoded as Windows-1252.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mojibake {
+    /// Byte offset of the sequence in the input.
+    pub position: usize,
+    /// The sequence as it appears in the input, e.g. `â€™`.
+    pub sequence: String,
+    /// The character the sequence was most likely meant to be, e.g. `’`.
+    pub repaired: char,
+}
+
+/// Find suspected mojibake in `input` without modifying it. A run of characters counts as
+/// mojibake when their Windows-1252 bytes form a single valid multi-byte UTF-8 character.
+pub fn detect_mojibake(input: &str) -> Vec<Mojibake> {
+    let mut found = Vec::new();
+    let mut position = 0;
+    while let Some(c) = input[position..].chars().next() {
+        match repair_at(&input[position..]) {
+            Some((len, repaired)) => {
+                found.push(Mojibake {
+                    position,
+                    sequence: input[position..position + len].to_string(),
+                    repaired,
+                });
+                position += len;
+            }
+            None => position += c.len_utf8(),
+        }
+    }
+    found
+}
+
+/// If `rest` starts with mojibake, the byte length of the mojibake and its repair.
+fn repair_at(rest: &str) -> Option<(usize, char)> {
+    let mut chars = rest.char_indices();
+    let (_, first) = chars.next()?;
+    let lead = windows_1252_byte(first)?;
+    let width = match lead {
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return None
+    };
+    let mut bytes = [lead, 0, 0, 0];
+    for byte in bytes.iter_mut().take(width).skip(1) {
+        let (_, c) = chars.next()?;
+        match windows_1252_byte(c) {
+            Some(b @ 0x80..=0xBF) => *byte = b,
+            _ => return None
+        }
+    }
+    let len = chars.next().map_or(rest.len(), |(index, _)| index);
+    // from_utf8 rejects overlong forms and surrogates for us
+    let repaired = std::str::from_utf8(&bytes[..width]).ok()?.chars().next()?;
+    Some((len, repaired))
+}
+
+/// The byte Windows-1252 encodes `c` as. The five bytes Windows-1252 leaves undefined are
+/// commonly passed through as the matching C1 control, so those are accepted too.
+fn windows_1252_byte(c: char) -> Option<u8> {
+    match c as u32 {
+        0x00..=0x7F | 0xA0..=0xFF => Some(c as u8),
+        0x81 | 0x8D | 0x8F | 0x90 | 0x9D => Some(c as u8),
+        _ => {
+            let byte = match c {
+                '€' => 0x80,
+                '‚' => 0x82,
+                'ƒ' => 0x83,
+                '„' => 0x84,
+                '…' => 0x85,
+                '†' => 0x86,
+                '‡' => 0x87,
+                'ˆ' => 0x88,
+                '‰' => 0x89,
+                'Š' => 0x8A,
+                '‹' => 0x8B,
+                'Œ' => 0x8C,
+                'Ž' => 0x8E,
+                '‘' => 0x91,
+                '’' => 0x92,
+                '“' => 0x93,
+                '”' => 0x94,
+                '•' => 0x95,
+                '–' => 0x96,
+                '—' => 0x97,
+                '˜' => 0x98,
+                '™' => 0x99,
+                'š' => 0x9A,
+                '›' => 0x9B,
+                'œ' => 0x9C,
+                'ž' => 0x9E,
+                'Ÿ' => 0x9F,
+                _ => return None
+            };
+            Some(byte)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_text_has_no_mojibake() {
+        assert!(detect_mojibake("It’s a “quoted” café — fine").is_empty());
+    }
+
+    #[test]
+    fn misdecoded_quote_is_found() {
+        assert_eq!(vec![Mojibake { position: 2, sequence: "â€™".to_string(), repaired: '’' }],
+                   detect_mojibake("Itâ€™s"));
+    }
+
+    #[test]
+    fn every_sequence_is_reported_with_its_position() {
+        let found = detect_mojibake("cafÃ© â€œhiâ€\u{9d} ðŸ˜€");
+        let summary: Vec<(usize, char)> = found.iter().map(|m| (m.position, m.repaired)).collect();
+        assert_eq!(vec![(3, 'é'), (8, '“'), (17, '”'), (25, '😀')], summary);
+    }
+
+    #[test]
+    fn incomplete_sequence_is_ignored() {
+        assert!(detect_mojibake("Ã and â€").is_empty());
+    }
+}
@@ -0,0 +1,196 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::iter;
+use thiserror::Error;
+
+use super::charsub::{unescape, UnescapeError};
+use super::diagnostic::Diagnostic;
+
+#[derive(Error, Debug)]
+pub enum TokenSubError {
+    #[error("Empty token in substitution with output: {0}")]
+    EmptyInput(String),
+    #[error("Token contains a delimiter and can never match: {0}{1}")]
+    InputContainsDelimiter(String, char),
+    #[error(transparent)]
+    Unescape(#[from] UnescapeError)
+}
+
+impl Diagnostic for TokenSubError {
+    fn code(&self) -> &'static str {
+        match self {
+            TokenSubError::EmptyInput(..) => "CS0008",
+            TokenSubError::InputContainsDelimiter(..) => "CS0009",
+            TokenSubError::Unescape(error) => error.code(),
+        }
+    }
+
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        match self {
+            TokenSubError::EmptyInput(output) => vec![("output", output.clone())],
+            TokenSubError::InputContainsDelimiter(context, found) => {
+                vec![("context", context.clone()), ("found", found.to_string())]
+            }
+            TokenSubError::Unescape(error) => error.fields(),
+        }
+    }
+}
+
+/// Substitutes whole tokens, where tokens are the runs of text between delimiter characters.
+/// A token is only replaced when it is exactly the input of a substitution, e.g. `{{name}}`.
+#[derive(Debug, Default)]
+pub struct TokenSubMachine {
+    substitutions: HashMap<String, String>,
+    // None means any whitespace
+    delimiters: Option<Vec<char>>,
+}
+
+impl TokenSubMachine {
+    /// A machine whose tokens are separated by whitespace.
+    pub fn new() -> TokenSubMachine {
+        TokenSubMachine::default()
+    }
+
+    /// A machine whose tokens are separated by any of `delimiters`.
+    pub fn with_delimiters(delimiters: &[char]) -> TokenSubMachine {
+        TokenSubMachine {
+            substitutions: HashMap::new(),
+            delimiters: Some(delimiters.to_vec()),
+        }
+    }
+
+    /// Replace the token `input` with `output`. Both are unescaped first, so rules can be
+    /// written the same way as for the character substitution machinery.
+    pub fn add_substitution(&mut self, input: &str, output: &str) -> Result<(), TokenSubError> {
+        let input = unescape(input)?;
+        let output = unescape(output)?;
+        if input.is_empty() {
+            return Err(TokenSubError::EmptyInput(output.into_owned()));
+        }
+        if let Some((index, ch)) = input.char_indices().find(|&(_, ch)| self.is_delimiter(ch)) {
+            return Err(TokenSubError::InputContainsDelimiter(input[..index].to_string(), ch));
+        }
+        self.substitutions.insert(input.into_owned(), output.into_owned());
+        Ok(())
+    }
+
+    pub fn process<'a>(&self, input: &'a str) -> Cow<'a, str> {
+        // only allocated once a token is actually replaced
+        let mut modified_string: Option<String> = None;
+        let mut token_start = 0;
+        let boundaries = input.char_indices()
+            .filter(|&(_, ch)| self.is_delimiter(ch))
+            .map(|(index, ch)| (index, index + ch.len_utf8()))
+            .chain(iter::once((input.len(), input.len())));
+
+        for (token_end, next_token_start) in boundaries {
+            let token = &input[token_start..token_end];
+            match self.substitutions.get(token) {
+                Some(output) => {
+                    modified_string
+                        .get_or_insert_with(|| {
+                            let mut modified = String::with_capacity(input.len());
+                            modified.push_str(&input[..token_start]);
+                            modified
+                        })
+                        .push_str(output);
+                }
+                None => {
+                    if let Some(modified) = modified_string.as_mut() {
+                        modified.push_str(token);
+                    }
+                }
+            }
+            if let Some(modified) = modified_string.as_mut() {
+                modified.push_str(&input[token_end..next_token_start]);
+            }
+            token_start = next_token_start;
+        }
+
+        match modified_string {
+            Some(modified) => modified.into(),
+            None => input.into()
+        }
+    }
+
+    fn is_delimiter(&self, ch: char) -> bool {
+        match &self.delimiters {
+            Some(delimiters) => delimiters.contains(&ch),
+            None => ch.is_whitespace()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_tokens_are_replaced() -> anyhow::Result<()> {
+        let mut machine = TokenSubMachine::new();
+        machine.add_substitution("{{name}}", "World")?;
+        assert_eq!("Hello,\tWorld  {{name}}s", machine.process("Hello,\t{{name}}  {{name}}s"));
+        Ok(())
+    }
+
+    #[test]
+    fn unchanged_input_is_borrowed() -> anyhow::Result<()> {
+        let mut machine = TokenSubMachine::new();
+        machine.add_substitution("{{name}}", "World")?;
+        assert!(matches!(machine.process("Hello there"), Cow::Borrowed("Hello there")));
+        Ok(())
+    }
+
+    #[test]
+    fn custom_delimiters_are_used() -> anyhow::Result<()> {
+        let mut machine = TokenSubMachine::with_delimiters(&[' ', ',', '!']);
+        machine.add_substitution("{{name}}", "World")?;
+        machine.add_substitution("Hello", "Bonjour")?;
+        assert_eq!("Bonjour, World!", machine.process("Hello, {{name}}!"));
+        Ok(())
+    }
+
+    #[test]
+    fn substitutions_are_unescaped() -> anyhow::Result<()> {
+        let mut machine = TokenSubMachine::new();
+        machine.add_substitution("\\u{2014}", "--")?;
+        machine.add_substitution("nbsp", "\\u{a0}")?;
+        assert_eq!("a -- b", machine.process("a \u{2014} b"));
+        assert_eq!("x \u{a0} y", machine.process("x nbsp y"));
+        Ok(())
+    }
+
+    #[test]
+    fn empty_input_gives_error() {
+        let error = TokenSubMachine::new().add_substitution("", "x").unwrap_err();
+        assert_eq!("CS0008", error.code());
+        assert_eq!("Empty token in substitution with output: x", format!("{}", error))
+    }
+
+    #[test]
+    fn input_containing_delimiter_gives_error() {
+        let error = TokenSubMachine::new().add_substitution("a b", "x").unwrap_err();
+        assert_eq!("CS0009", error.code());
+        assert_eq!("Token contains a delimiter and can never match: a ", format!("{}", error))
+    }
+
+    #[test]
+    fn bad_escape_in_rule_gives_unescape_error() {
+        let error = TokenSubMachine::new().add_substitution("a\\q", "x").unwrap_err();
+        assert_eq!("CS0001", error.code());
+        assert_eq!("Bad escape parsing string. Failed at: a\\q", format!("{}", error));
+        assert_eq!(vec![("context", "a\\".to_string()), ("found", "q".to_string())], error.fields());
+    }
+
+    #[test]
+    fn errors_are_diagnostics() {
+        let error = TokenSubError::EmptyInput("x".to_string());
+        assert_eq!("CS0008", error.code());
+        assert_eq!(r#"{"code":"CS0008","message":"Empty token in substitution with output: x","output":"x"}"#, error.to_json());
+        let error = TokenSubError::InputContainsDelimiter("a".to_string(), ' ');
+        assert_eq!("CS0009", error.code());
+        let mut catalog = HashMap::new();
+        catalog.insert("CS0009", "« {context} » contient le délimiteur « {found} »");
+        assert_eq!("« a » contient le délimiteur «   »", error.localize(&catalog));
+    }
+}
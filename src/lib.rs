@@ -1 +1,6 @@
-mod finl;
\ No newline at end of file
+mod finl;
+pub mod prelude;
+#[cfg(feature = "unstable")]
+pub mod unstable;
+
+pub use prelude::*;
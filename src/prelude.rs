@@ -0,0 +1,8 @@
+//! The small, stable surface of the crate, for `use finl_charsub::prelude::*;`. Everything
+//! here is also re-exported from the crate root.
+
+pub use crate::finl::charsub::{
+    unescape, unescape_with_options, unescaped_len, unescaped_len_with_options, validate_escapes,
+    validate_escapes_with_options, UnescapeError, UnescapeOptions,
+};
+pub use crate::finl::diagnostic::{Diagnostic, MessageCatalog};
@@ -0,0 +1,6 @@
+//! Newer pieces of the crate which are not yet part of the stable surface in
+//! [`prelude`](crate::prelude) and may change in any release.
+//! Only built with the `unstable` feature.
+
+pub use crate::finl::mojibake::{detect_mojibake, Mojibake};
+pub use crate::finl::tokensub::{TokenSubError, TokenSubMachine};